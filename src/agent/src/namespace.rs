@@ -6,28 +6,37 @@
 use anyhow::{anyhow, Result, Context};
 use nix::fcntl::{self, OFlag};
 use nix::mount::MsFlags;
-use nix::sched::{unshare, CloneFlags};
+use nix::sched::{setns, unshare, CloneFlags};
 use nix::sys::stat::Mode;
 use nix::sys::wait::wait;
 use nix::unistd::{fork, ForkResult, getpid, gettid, self, Uid, Gid};
 use oci::LinuxIdMapping;
 use protocols::oci::LinuxIDMapping;
 use protocols::trans::from_vec;
+use serde::{Deserialize, Serialize};
 use slog::Logger;
 use std::fmt;
 use std::fs;
 use std::fs::File;
+use std::os::unix::io::AsRawFd;
 use std::path::{Path, PathBuf};
 use tracing::instrument;
 use rustjail::sync::{read_sync, write_sync, SYNC_SUCCESS};
 use crate::mount::baremount;
 
+// From <linux/nsfs.h>: retrieves the CLONE_NEW* flag identifying the type of
+// an open namespace file, so a namespace entered via setns(2) can be
+// validated against the type the caller asked for.
+nix::ioctl_none!(ns_get_nstype, 0xb7, 0x3);
+
 const PERSISTENT_NS_DIR: &str = "/var/run/sandbox-ns";
 pub const NSTYPEIPC: &str = "ipc";
 pub const NSTYPEUTS: &str = "uts";
 pub const NSTYPEPID: &str = "pid";
 pub const NSTYPEUSER: &str = "user";
 pub const NSTYPENET: &str = "network";
+pub const NSTYPETIME: &str = "time";
+pub const NSTYPECGROUP: &str = "cgroup";
 
 #[instrument]
 fn get_proc_ns_path(pid: i32, ns_type: &str) -> String {
@@ -78,6 +87,24 @@ fn setid(uid: Uid, gid: Gid) -> Result<()> {
     Ok(())
 }
 
+// Write the clock offsets for a time namespace. This can only be done once,
+// after unshare(CLONE_NEWTIME) and before any process (including the one that
+// called unshare) enters the namespace, e.g. by forking.
+#[instrument]
+fn write_timens_offsets(tid: i32, offsets: &TimeNsOffsets) -> Result<()> {
+    let path = format!("/proc/{}/timens_offsets", tid);
+    let data = format!(
+        "monotonic {} {}\nboottime {} {}\n",
+        offsets.monotonic.0, offsets.monotonic.1, offsets.boottime.0, offsets.boottime.1
+    );
+
+    let fd = fcntl::open(path.as_str(), OFlag::O_WRONLY, Mode::empty())?;
+    defer!(unistd::close(fd).unwrap());
+    unistd::write(fd, data.as_bytes())?;
+
+    Ok(())
+}
+
 #[instrument]
 fn write_mappings(logger: &Logger, path: &str, maps: &[LinuxIdMapping]) -> Result<()> {
     let data = maps
@@ -99,6 +126,15 @@ fn write_mappings(logger: &Logger, path: &str, maps: &[LinuxIdMapping]) -> Resul
     Ok(())
 }
 
+/// Clock offsets applied to a time namespace via `/proc/<tid>/timens_offsets`,
+/// expressed as (seconds, nanoseconds) pairs. The offsets can only be set
+/// while the namespace has no members, i.e. right after `unshare(CLONE_NEWTIME)`.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct TimeNsOffsets {
+    pub monotonic: (i64, i64),
+    pub boottime: (i64, i64),
+}
+
 #[derive(Debug)]
 pub struct Namespace {
     logger: Logger,
@@ -109,6 +145,8 @@ pub struct Namespace {
     pub hostname: Option<String>,
     pub uid_mappings: Option<Vec<LinuxIDMapping>>,
     pub gid_mappings: Option<Vec<LinuxIDMapping>>,
+    //only used for time namespace
+    pub time_offsets: Option<TimeNsOffsets>,
 }
 
 impl Namespace {
@@ -122,6 +160,7 @@ impl Namespace {
             hostname: None,
             uid_mappings: None,
             gid_mappings: None,
+            time_offsets: None,
         }
     }
 
@@ -165,12 +204,35 @@ impl Namespace {
         self
     }
 
+    #[instrument]
+    pub fn get_time(mut self, offsets: Option<TimeNsOffsets>) -> Self {
+        self.ns_type = NamespaceType::Time;
+        self.time_offsets = offsets;
+        self
+    }
+
+    #[instrument]
+    pub fn get_cgroup(mut self) -> Self {
+        self.ns_type = NamespaceType::Cgroup;
+        self
+    }
+
     #[allow(dead_code)]
     pub fn set_root_dir(mut self, dir: &str) -> Self {
         self.persistent_ns_dir = dir.to_string();
         self
     }
 
+    /// Point this namespace descriptor at an already-persisted namespace
+    /// file, e.g. a CNI-created netns under /var/run/netns. Combine with
+    /// `enter()`/`join()` instead of `setup()` to attach to it rather than
+    /// creating a fresh namespace.
+    #[instrument]
+    pub fn from_path(mut self, path: &str) -> Self {
+        self.path = path.to_string();
+        self
+    }
+
     // setup creates persistent namespace without switching to it.
     // Note, pid namespaces cannot be persisted.
     #[instrument]
@@ -191,10 +253,21 @@ impl Namespace {
 
         self.path = new_ns_path.clone().into_os_string().into_string().unwrap();
         let hostname = self.hostname.clone();
+        let time_offsets = self.time_offsets;
 
         let new_thread = std::thread::spawn(move || {
             if let Err(err) = || -> Result<()> {
-                let origin_ns_path = get_current_thread_ns_path(ns_type.get());
+                // unshare(CLONE_NEWTIME) does not move the calling thread into
+                // the new time namespace (see time_namespaces(7)): it only sets
+                // up the namespace that will apply to the thread's *future*
+                // children, exposed at ns/time_for_children. ns/time still
+                // refers to the old namespace, so persisting it would bind-mount
+                // the wrong namespace and silently drop the offsets just written.
+                let origin_ns_path = if ns_type == NamespaceType::Time {
+                    get_current_thread_ns_path("time_for_children")
+                } else {
+                    get_current_thread_ns_path(ns_type.get())
+                };
 
                 let source = Path::new(&origin_ns_path);
                 let destination = new_ns_path.as_path();
@@ -209,6 +282,14 @@ impl Namespace {
                 if ns_type == NamespaceType::Uts && hostname.is_some() {
                     nix::unistd::sethostname(hostname.unwrap())?;
                 }
+
+                // The time namespace has no members yet, so this is the only
+                // window in which the clock offsets can be written.
+                if ns_type == NamespaceType::Time {
+                    if let Some(offsets) = time_offsets {
+                        write_timens_offsets(gettid().as_raw(), &offsets)?;
+                    }
+                }
                 // Bind mount the new namespace from the current thread onto the mount point to persist it.
 
                 let mut flags = MsFlags::empty();
@@ -237,6 +318,70 @@ impl Namespace {
 
         Ok(self)
     }
+
+    /// Enter an already-persisted namespace file at `self.path` (set via
+    /// `from_path()`) with setns(2), instead of creating a new namespace with
+    /// unshare(2), and run `work` to completion on the thread that entered
+    /// it. The namespace file's type is validated with NS_GET_NSTYPE before
+    /// entering it.
+    ///
+    /// Namespace membership is per-thread: by the time `enter()` returns,
+    /// the thread that called setns(2) has already exited, so there is no
+    /// way to hand the joined namespace back to the caller's own thread or
+    /// async task. Anything that needs to observe the namespace (creating
+    /// sockets in it, reading its routes, etc.) must happen inside `work`.
+    ///
+    /// As with `setup()`, this (and `work`) runs on a dedicated thread so the
+    /// tokio worker that called it never changes namespace. The agent is
+    /// still a multithreaded process, and setns(2) refuses CLONE_NEWUSER for
+    /// a multithreaded caller, so a user namespace cannot be entered this way.
+    #[instrument(skip(work))]
+    pub async fn enter<F, T>(self, work: F) -> Result<T>
+    where
+        F: FnOnce() -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let ns_type = self.ns_type;
+        let path = self.path.clone();
+
+        let new_thread = std::thread::spawn(move || -> Result<T> {
+            let file = File::open(&path)
+                .map_err(|e| anyhow!("failed to open namespace file {:?}: {:?}", path, e))?;
+            let fd = file.as_raw_fd();
+
+            let raw_nstype = unsafe { ns_get_nstype(fd) }
+                .map_err(|e| anyhow!("NS_GET_NSTYPE failed for {:?}: {:?}", path, e))?;
+            let want_flags = ns_type.get_flags();
+            let got_flags = CloneFlags::from_bits_truncate(raw_nstype);
+            if got_flags != want_flags {
+                return Err(anyhow!(
+                    "namespace file {:?} has type {:?}, expected {:?}",
+                    path,
+                    got_flags,
+                    want_flags
+                ));
+            }
+
+            setns(fd, want_flags)
+                .map_err(|e| anyhow!("setns failed for {:?}: {:?}", path, e))?;
+
+            work()
+        });
+
+        new_thread
+            .join()
+            .map_err(|e| anyhow!("Failed to join thread {:?}!", e))?
+    }
+
+    /// Alias for `enter()`, matching the terminology used by `ip netns exec`.
+    #[instrument(skip(work))]
+    pub async fn join<F, T>(self, work: F) -> Result<T>
+    where
+        F: FnOnce() -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        self.enter(work).await
+    }
 }
 
 /// setup persistent namespace in a non-root user namespace without switching to it.
@@ -285,7 +430,13 @@ pub async fn setup_in_userns(
                 File::create(new_ns_path.as_path())?;
                 ns.path = new_ns_path.clone().into_os_string().into_string().unwrap();
 
-                let origin_ns_path = get_proc_ns_path(child.as_raw(), ns_type.get());
+                // As in setup(), persisting a time namespace needs
+                // ns/time_for_children, not ns/time; see the comment there.
+                let origin_ns_path = if ns_type == NamespaceType::Time {
+                    get_proc_ns_path(child.as_raw(), "time_for_children")
+                } else {
+                    get_proc_ns_path(child.as_raw(), ns_type.get())
+                };
 
                 mounts.push((origin_ns_path, new_ns_path));
             }
@@ -357,6 +508,14 @@ pub async fn setup_in_userns(
                 if ns.ns_type == NamespaceType::Uts && hostname.is_some() {
                     nix::unistd::sethostname(hostname.unwrap())?;
                 }
+
+                // The time namespace has no members yet, so this is the only
+                // window in which the clock offsets can be written.
+                if ns.ns_type == NamespaceType::Time {
+                    if let Some(offsets) = ns.time_offsets {
+                        write_timens_offsets(gettid().as_raw(), &offsets)?;
+                    }
+                }
             }
 
             // notify parent to persist namespace
@@ -379,13 +538,16 @@ pub async fn setup_in_userns(
 }
 
 /// Represents the Namespace type.
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 enum NamespaceType {
     Ipc,
     Uts,
     Pid,
     User,
     Net,
+    Time,
+    Cgroup,
 }
 
 impl NamespaceType {
@@ -397,6 +559,8 @@ impl NamespaceType {
             Self::Pid => "pid",
             Self::User => "user",
             Self::Net => "net",
+            Self::Time => "time",
+            Self::Cgroup => "cgroup",
         }
     }
 
@@ -408,8 +572,208 @@ impl NamespaceType {
             Self::Pid => CloneFlags::CLONE_NEWPID,
             Self::User => CloneFlags::CLONE_NEWUSER,
             Self::Net => CloneFlags::CLONE_NEWNET,
+            Self::Time => CloneFlags::CLONE_NEWTIME,
+            Self::Cgroup => CloneFlags::CLONE_NEWCGROUP,
+        }
+    }
+}
+
+/// A single id mapping as captured for checkpoint. Kept independent of both
+/// `oci::LinuxIdMapping` and `protocols::oci::LinuxIDMapping` so the on-disk
+/// manifest format doesn't move with either crate's wire representation.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct IdMappingRecord {
+    pub container_id: u32,
+    pub host_id: u32,
+    pub size: u32,
+}
+
+impl From<&LinuxIDMapping> for IdMappingRecord {
+    fn from(m: &LinuxIDMapping) -> Self {
+        IdMappingRecord {
+            container_id: m.container_id,
+            host_id: m.host_id,
+            size: m.size,
+        }
+    }
+}
+
+impl From<&IdMappingRecord> for LinuxIDMapping {
+    fn from(r: &IdMappingRecord) -> Self {
+        LinuxIDMapping {
+            container_id: r.container_id,
+            host_id: r.host_id,
+            size: r.size,
+            ..Default::default()
+        }
+    }
+}
+
+/// On-disk record of one persisted namespace, enough to re-create an
+/// equivalent `Namespace` on restore without talking to the process it came
+/// from.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NamespaceRecord {
+    ns_type: NamespaceType,
+    pub path: String,
+    pub hostname: Option<String>,
+    pub uid_mappings: Vec<IdMappingRecord>,
+    pub gid_mappings: Vec<IdMappingRecord>,
+    pub time_offsets: Option<TimeNsOffsets>,
+}
+
+impl NamespaceRecord {
+    fn from_namespace(ns: &Namespace) -> Result<Self> {
+        if ns.ns_type == NamespaceType::Pid {
+            return Err(anyhow!("Cannot persist namespace of PID type"));
+        }
+
+        Ok(NamespaceRecord {
+            ns_type: ns.ns_type,
+            path: ns.path.clone(),
+            hostname: ns.hostname.clone(),
+            uid_mappings: ns
+                .uid_mappings
+                .as_deref()
+                .unwrap_or_default()
+                .iter()
+                .map(IdMappingRecord::from)
+                .collect(),
+            gid_mappings: ns
+                .gid_mappings
+                .as_deref()
+                .unwrap_or_default()
+                .iter()
+                .map(IdMappingRecord::from)
+                .collect(),
+            time_offsets: ns.time_offsets,
+        })
+    }
+}
+
+/// Stable on-disk manifest of a sandbox's namespace set, written by
+/// `checkpoint_namespaces()` and read back by `restore_namespaces()`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct NamespaceManifest {
+    namespaces: Vec<NamespaceRecord>,
+}
+
+/// Serialize `namespaces` into a manifest at `manifest_path`. Mirrors CRIU's
+/// model of dumping a namespace tree: each persisted namespace's type,
+/// bind-mount path, hostname and id mappings are captured so the set can be
+/// reconstructed on another host. PID namespaces are rejected, as they were
+/// never persistable in the first place.
+#[instrument(skip(namespaces))]
+pub fn checkpoint_namespaces(manifest_path: &Path, namespaces: &[&Namespace]) -> Result<()> {
+    let records = namespaces
+        .iter()
+        .map(|ns| NamespaceRecord::from_namespace(ns))
+        .collect::<Result<Vec<_>>>()?;
+
+    let manifest = NamespaceManifest {
+        namespaces: records,
+    };
+
+    let data = serde_json::to_vec_pretty(&manifest)
+        .context("failed to serialize namespace manifest")?;
+    fs::write(manifest_path, data)
+        .with_context(|| format!("failed to write namespace manifest to {:?}", manifest_path))?;
+
+    Ok(())
+}
+
+fn persistent_dir_of(path: &str) -> Result<String> {
+    Path::new(path)
+        .parent()
+        .and_then(|p| p.to_str())
+        .map(String::from)
+        .ok_or_else(|| anyhow!("namespace path {:?} has no parent directory", path))
+}
+
+fn namespace_from_record(logger: &Logger, record: &NamespaceRecord) -> Result<Namespace> {
+    let root_dir = persistent_dir_of(&record.path)?;
+
+    let ns = match record.ns_type {
+        NamespaceType::Ipc => Namespace::new(logger).get_ipc(),
+        NamespaceType::Uts => {
+            Namespace::new(logger).get_uts(record.hostname.as_deref().unwrap_or(""))
+        }
+        NamespaceType::Net => Namespace::new(logger).get_net(),
+        NamespaceType::Time => Namespace::new(logger).get_time(record.time_offsets),
+        NamespaceType::Cgroup => Namespace::new(logger).get_cgroup(),
+        NamespaceType::User => {
+            return Err(anyhow!(
+                "user namespace must be restored via restore_namespaces' primary path"
+            ))
+        }
+        NamespaceType::Pid => return Err(anyhow!("Cannot persist namespace of PID type")),
+    };
+
+    Ok(ns.set_root_dir(&root_dir))
+}
+
+/// Re-create the namespace set recorded in `manifest_path`.
+///
+/// The user namespace, if the set has one, is recreated first: its id
+/// mappings are replayed through the same fork/pipe handshake and
+/// `write_mappings` call that `setup_in_userns` already uses, since every
+/// dependent namespace in the set is unshared from inside that same child.
+/// The rest are then re-established and re-bound under the directory they
+/// were persisted under. A manifest containing a PID namespace is rejected,
+/// since PID namespaces were never persistable to begin with.
+#[instrument(skip(logger))]
+pub async fn restore_namespaces(logger: &Logger, manifest_path: &Path) -> Result<Vec<Namespace>> {
+    let data = fs::read(manifest_path)
+        .with_context(|| format!("failed to read namespace manifest from {:?}", manifest_path))?;
+    let manifest: NamespaceManifest =
+        serde_json::from_slice(&data).context("failed to parse namespace manifest")?;
+
+    if manifest
+        .namespaces
+        .iter()
+        .any(|r| r.ns_type == NamespaceType::Pid)
+    {
+        return Err(anyhow!(
+            "namespace manifest contains a non-persistable PID namespace"
+        ));
+    }
+
+    let (user_records, other_records): (Vec<_>, Vec<_>) = manifest
+        .namespaces
+        .into_iter()
+        .partition(|r| r.ns_type == NamespaceType::User);
+
+    let mut restored = Vec::new();
+
+    match user_records.into_iter().next() {
+        Some(user_record) => {
+            let uid_mappings = user_record.uid_mappings.iter().map(LinuxIDMapping::from).collect();
+            let gid_mappings = user_record.gid_mappings.iter().map(LinuxIDMapping::from).collect();
+            let root_dir = persistent_dir_of(&user_record.path)?;
+
+            let mut userns = Namespace::new(logger)
+                .get_user(uid_mappings, gid_mappings)
+                .set_root_dir(&root_dir);
+
+            let mut others = other_records
+                .iter()
+                .map(|r| namespace_from_record(logger, r))
+                .collect::<Result<Vec<_>>>()?;
+
+            setup_in_userns(logger, &mut userns, others.iter_mut().collect()).await?;
+
+            restored.push(userns);
+            restored.append(&mut others);
+        }
+        None => {
+            for record in &other_records {
+                let ns = namespace_from_record(logger, record)?.setup().await?;
+                restored.push(ns);
+            }
         }
     }
+
+    Ok(restored)
 }
 
 impl fmt::Debug for NamespaceType {
@@ -420,9 +784,13 @@ impl fmt::Debug for NamespaceType {
 
 #[cfg(test)]
 mod tests {
-    use super::{Namespace, NamespaceType};
+    use super::{
+        checkpoint_namespaces, restore_namespaces, Namespace, NamespaceManifest, NamespaceType,
+        TimeNsOffsets,
+    };
     use crate::mount::remove_mounts;
     use nix::sched::CloneFlags;
+    use std::fs;
     use tempfile::Builder;
     use test_utils::skip_if_not_root;
 
@@ -467,6 +835,107 @@ mod tests {
         assert!(ns_pid.is_err());
     }
 
+    #[tokio::test]
+    async fn test_enter_persistent_ns() {
+        skip_if_not_root!();
+        // Create dummy logger and temp folder.
+        let logger = slog::Logger::root(slog::Discard, o!());
+        let tmpdir = Builder::new().prefix("ipc").tempdir().unwrap();
+
+        let ns_ipc = Namespace::new(&logger)
+            .get_ipc()
+            .set_root_dir(tmpdir.path().to_str().unwrap())
+            .setup()
+            .await
+            .unwrap();
+
+        let entered = Namespace::new(&logger)
+            .get_ipc()
+            .from_path(&ns_ipc.path)
+            .enter(|| Ok(()))
+            .await;
+
+        assert!(entered.is_ok());
+        assert!(remove_mounts(&[ns_ipc.path]).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_restore_namespaces() {
+        skip_if_not_root!();
+        let logger = slog::Logger::root(slog::Discard, o!());
+        let tmpdir = Builder::new().prefix("ckpt").tempdir().unwrap();
+        let root_dir = tmpdir.path().to_str().unwrap();
+
+        let ns_uts = Namespace::new(&logger)
+            .get_uts("checkpoint-test")
+            .set_root_dir(root_dir)
+            .setup()
+            .await
+            .unwrap();
+
+        let manifest_path = tmpdir.path().join("manifest.json");
+        checkpoint_namespaces(&manifest_path, &[&ns_uts]).unwrap();
+        assert!(remove_mounts(&[ns_uts.path.clone()]).is_ok());
+
+        let restored = restore_namespaces(&logger, &manifest_path).await.unwrap();
+        assert_eq!(1, restored.len());
+        assert_eq!(Some("checkpoint-test".to_string()), restored[0].hostname);
+
+        let paths = restored.iter().map(|ns| ns.path.clone()).collect::<Vec<_>>();
+        assert!(remove_mounts(&paths).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_restore_time_namespace_offsets() {
+        skip_if_not_root!();
+        let logger = slog::Logger::root(slog::Discard, o!());
+        let tmpdir = Builder::new().prefix("ckpt-time").tempdir().unwrap();
+        let root_dir = tmpdir.path().to_str().unwrap();
+
+        let offsets = TimeNsOffsets {
+            monotonic: (100, 0),
+            boottime: (200, 0),
+        };
+
+        let ns_time = Namespace::new(&logger)
+            .get_time(Some(offsets))
+            .set_root_dir(root_dir)
+            .setup()
+            .await
+            .unwrap();
+
+        let manifest_path = tmpdir.path().join("manifest.json");
+        checkpoint_namespaces(&manifest_path, &[&ns_time]).unwrap();
+        assert!(remove_mounts(&[ns_time.path.clone()]).is_ok());
+
+        let manifest_data = fs::read(&manifest_path).unwrap();
+        let manifest: NamespaceManifest = serde_json::from_slice(&manifest_data).unwrap();
+        assert_eq!(
+            Some(offsets.monotonic),
+            manifest.namespaces[0].time_offsets.map(|o| o.monotonic)
+        );
+        assert_eq!(
+            Some(offsets.boottime),
+            manifest.namespaces[0].time_offsets.map(|o| o.boottime)
+        );
+
+        let restored = restore_namespaces(&logger, &manifest_path).await.unwrap();
+        assert_eq!(1, restored.len());
+
+        let paths = restored.iter().map(|ns| ns.path.clone()).collect::<Vec<_>>();
+        assert!(remove_mounts(&paths).is_ok());
+    }
+
+    #[test]
+    fn test_checkpoint_rejects_pid_namespace() {
+        let logger = slog::Logger::root(slog::Discard, o!());
+        let ns_pid = Namespace::new(&logger).get_pid();
+        let tmpdir = Builder::new().prefix("ckpt-pid").tempdir().unwrap();
+        let manifest_path = tmpdir.path().join("manifest.json");
+
+        assert!(checkpoint_namespaces(&manifest_path, &[&ns_pid]).is_err());
+    }
+
     #[test]
     fn test_namespace_type() {
         let ipc = NamespaceType::Ipc;
@@ -531,6 +1000,39 @@ mod tests {
         assert_eq!(NamespaceType::Pid, ns_pid.ns_type);
     }
 
+    #[test]
+    fn test_get_time() {
+        // Create dummy logger and temp folder.
+        let logger = slog::Logger::root(slog::Discard, o!());
+
+        let ns_time = Namespace::new(&logger).get_time(None);
+        assert_eq!(NamespaceType::Time, ns_time.ns_type);
+        assert!(ns_time.time_offsets.is_none());
+    }
+
+    #[test]
+    fn test_get_time_with_offsets() {
+        let offsets = TimeNsOffsets {
+            monotonic: (100, 0),
+            boottime: (100, 0),
+        };
+        // Create dummy logger and temp folder.
+        let logger = slog::Logger::root(slog::Discard, o!());
+
+        let ns_time = Namespace::new(&logger).get_time(Some(offsets));
+        assert_eq!(NamespaceType::Time, ns_time.ns_type);
+        assert!(ns_time.time_offsets.is_some());
+    }
+
+    #[test]
+    fn test_get_cgroup() {
+        // Create dummy logger and temp folder.
+        let logger = slog::Logger::root(slog::Discard, o!());
+
+        let ns_cgroup = Namespace::new(&logger).get_cgroup();
+        assert_eq!(NamespaceType::Cgroup, ns_cgroup.ns_type);
+    }
+
     #[test]
     fn test_set_root_dir() {
         // Create dummy logger and temp folder.
@@ -542,6 +1044,18 @@ mod tests {
         assert_eq!(ns_root.persistent_ns_dir, tmpdir.path().to_str().unwrap());
     }
 
+    #[test]
+    fn test_from_path() {
+        // Create dummy logger and temp folder.
+        let logger = slog::Logger::root(slog::Discard, o!());
+
+        let ns_net = Namespace::new(&logger)
+            .get_net()
+            .from_path("/proc/self/ns/net");
+        assert_eq!(NamespaceType::Net, ns_net.ns_type);
+        assert_eq!(ns_net.path, "/proc/self/ns/net");
+    }
+
     #[test]
     fn test_namespace_type_get() {
         #[derive(Debug)]
@@ -563,6 +1077,14 @@ mod tests {
                 ns_type: NamespaceType::Pid,
                 str: "pid",
             },
+            TestData {
+                ns_type: NamespaceType::Time,
+                str: "time",
+            },
+            TestData {
+                ns_type: NamespaceType::Cgroup,
+                str: "cgroup",
+            },
         ];
 
         // Run the tests
@@ -594,6 +1116,14 @@ mod tests {
                 ns_type: NamespaceType::Pid,
                 ns_flag: CloneFlags::CLONE_NEWPID,
             },
+            TestData {
+                ns_type: NamespaceType::Time,
+                ns_flag: CloneFlags::CLONE_NEWTIME,
+            },
+            TestData {
+                ns_type: NamespaceType::Cgroup,
+                ns_flag: CloneFlags::CLONE_NEWCGROUP,
+            },
         ];
 
         // Run the tests
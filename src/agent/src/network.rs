@@ -0,0 +1,316 @@
+// Copyright (c) 2023 Ant Group
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+
+//! rtnetlink-based wiring for persisted network namespaces.
+//!
+//! `namespace::Namespace::get_net().setup()` only creates and bind-mounts an
+//! empty netns; nothing moves interfaces into it. This module closes that
+//! gap: given the resulting `Namespace` and a list of host links, it moves
+//! each link into the namespace by ifindex (IFLA_NET_NS_FD), brings it up,
+//! and assigns its addresses/routes, all over rtnetlink sockets rather than
+//! by shelling out to `ip`.
+
+use anyhow::{anyhow, Context, Result};
+use futures::stream::TryStreamExt;
+use ipnetwork::IpNetwork;
+use rtnetlink::{new_connection, Handle};
+use std::fs::File;
+use std::net::IpAddr;
+use std::os::unix::io::AsRawFd;
+use tracing::instrument;
+
+use crate::namespace::Namespace;
+
+/// A route to install inside the namespace, scoped to the link it rides on.
+#[derive(Clone, Debug)]
+pub struct RouteSpec {
+    pub destination: IpNetwork,
+    pub gateway: Option<IpAddr>,
+}
+
+/// A host link (veth/tap/macvlan end) to move into a persisted net
+/// namespace, plus what to configure on it once it is there.
+#[derive(Clone, Debug)]
+pub struct LinkSpec {
+    /// ifindex of the link on the host side, before it is moved.
+    pub ifindex: u32,
+    /// Name the link should have once it is inside the namespace.
+    /// Left unset to keep whatever name it already has.
+    pub name: Option<String>,
+    /// Addresses (CIDR) to assign to the link once it is moved and up.
+    pub addresses: Vec<IpNetwork>,
+    /// Routes to install via this link after its addresses are assigned.
+    pub routes: Vec<RouteSpec>,
+}
+
+/// Move `links` into the net namespace persisted by `ns` (as produced by
+/// `Namespace::new(logger).get_net().setup()`), bringing each one up and
+/// assigning its addresses/routes. This is the step that turns an empty,
+/// just-created namespace into one with guest-facing interfaces.
+///
+/// Takes `ns` by value because configuring the moved links needs a netlink
+/// socket that actually lives inside the namespace, and the only way to get
+/// one is `Namespace::enter()`, which consumes the namespace to run work on
+/// the dedicated thread that calls setns(2).
+#[instrument(skip(ns, links))]
+pub async fn wire_net_namespace(ns: Namespace, links: &[LinkSpec]) -> Result<()> {
+    let ns_file = File::open(&ns.path)
+        .with_context(|| format!("failed to open net namespace file {:?}", ns.path))?;
+    let ns_fd = ns_file.as_raw_fd();
+
+    // Move every link from the host side first, using a host-namespace
+    // rtnetlink socket: once a link is moved, its host-side ifindex is no
+    // longer reachable to issue RTM_SETLINK against.
+    let (host_conn, _, host_handle) =
+        new_connection().context("failed to open host rtnetlink socket")?;
+    let host_task = tokio::spawn(host_conn);
+
+    for link in links {
+        host_handle
+            .link()
+            .set(link.ifindex)
+            .setns_by_fd(ns_fd)
+            .execute()
+            .await
+            .with_context(|| format!("failed to move link {} into namespace", link.ifindex))?;
+    }
+    host_task.abort();
+
+    // Configuring the moved links needs a netlink socket that was created
+    // while already inside the target namespace, since setns(2) - and thus
+    // namespace membership - is per-thread, not per-socket. Run that part on
+    // the dedicated thread `Namespace::enter()` uses for setns(2), so we
+    // never drag a shared tokio worker into the sandbox's netns.
+    let links = links.to_vec();
+    ns.enter(move || -> Result<()> {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .context("failed to build in-namespace runtime")?;
+
+        rt.block_on(async {
+            let (conn, _, handle) =
+                new_connection().context("failed to open rtnetlink socket inside namespace")?;
+            let conn_task = tokio::spawn(conn);
+
+            for link in &links {
+                configure_link(&handle, link).await?;
+            }
+
+            conn_task.abort();
+            Ok(())
+        })
+    })
+    .await
+}
+
+#[instrument(skip(handle, link))]
+async fn configure_link(handle: &Handle, link: &LinkSpec) -> Result<()> {
+    // The kernel keeps a link's ifindex across a namespace move when that
+    // index is free in the target namespace (in a fresh netns only `lo`
+    // holds one), so the link is looked up there by its host-side ifindex.
+    let msg = handle
+        .link()
+        .get()
+        .match_index(link.ifindex)
+        .execute()
+        .try_next()
+        .await
+        .context("failed to query link after namespace move")?
+        .ok_or_else(|| anyhow!("link {} not found in namespace after move", link.ifindex))?;
+    let index = msg.header.index;
+
+    if let Some(name) = &link.name {
+        handle
+            .link()
+            .set(index)
+            .name(name.clone())
+            .execute()
+            .await
+            .with_context(|| format!("failed to rename link {} to {}", link.ifindex, name))?;
+    }
+
+    handle
+        .link()
+        .set(index)
+        .up()
+        .execute()
+        .await
+        .with_context(|| format!("failed to bring up link {}", link.ifindex))?;
+
+    for addr in &link.addresses {
+        handle
+            .address()
+            .add(index, addr.ip(), addr.prefix())
+            .execute()
+            .await
+            .with_context(|| format!("failed to add address {} to link {}", addr, link.ifindex))?;
+    }
+
+    for route in &link.routes {
+        add_route(handle, index, route).await?;
+    }
+
+    Ok(())
+}
+
+#[instrument(skip(handle))]
+async fn add_route(handle: &Handle, index: u32, route: &RouteSpec) -> Result<()> {
+    let request = handle.route().add().output_interface(index);
+
+    let result = match (route.destination, route.gateway) {
+        (IpNetwork::V4(net), Some(IpAddr::V4(gw))) => {
+            request
+                .v4()
+                .destination_prefix(net.ip(), net.prefix())
+                .gateway(gw)
+                .execute()
+                .await
+        }
+        (IpNetwork::V4(net), _) => {
+            request
+                .v4()
+                .destination_prefix(net.ip(), net.prefix())
+                .execute()
+                .await
+        }
+        (IpNetwork::V6(net), Some(IpAddr::V6(gw))) => {
+            request
+                .v6()
+                .destination_prefix(net.ip(), net.prefix())
+                .gateway(gw)
+                .execute()
+                .await
+        }
+        (IpNetwork::V6(net), _) => {
+            request
+                .v6()
+                .destination_prefix(net.ip(), net.prefix())
+                .execute()
+                .await
+        }
+    };
+
+    result.with_context(|| format!("failed to add route {} via link {}", route.destination, index))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rtnetlink::Handle;
+    use std::net::Ipv4Addr;
+    use std::str::FromStr;
+    use test_utils::skip_if_not_root;
+
+    /// Create a dummy link on the host (no real namespace move involved) so
+    /// `configure_link`/`add_route` can be exercised against a real, if
+    /// throwaway, netlink-visible interface.
+    async fn add_dummy_link(handle: &Handle, name: &str) -> u32 {
+        handle
+            .link()
+            .add()
+            .dummy(name.to_string())
+            .execute()
+            .await
+            .unwrap();
+
+        handle
+            .link()
+            .get()
+            .match_name(name.to_string())
+            .execute()
+            .try_next()
+            .await
+            .unwrap()
+            .unwrap()
+            .header
+            .index
+    }
+
+    async fn del_link(handle: &Handle, index: u32) {
+        let _ = handle.link().del(index).execute().await;
+    }
+
+    #[tokio::test]
+    async fn test_configure_link_renames_and_assigns_address() {
+        skip_if_not_root!();
+        let (conn, _, handle) = new_connection().unwrap();
+        tokio::spawn(conn);
+
+        let index = add_dummy_link(&handle, "kata-test-dum0").await;
+
+        let link = LinkSpec {
+            ifindex: index,
+            name: Some("kata-test-dum1".to_string()),
+            addresses: vec![IpNetwork::from_str("10.50.0.1/24").unwrap()],
+            routes: vec![],
+        };
+
+        assert!(configure_link(&handle, &link).await.is_ok());
+
+        // The link is looked up again by ifindex (not by its old name), so
+        // renaming it must not break the lookup.
+        let msg = handle
+            .link()
+            .get()
+            .match_index(index)
+            .execute()
+            .try_next()
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(msg.header.index, index);
+
+        del_link(&handle, index).await;
+    }
+
+    #[tokio::test]
+    async fn test_add_route_v4_without_gateway() {
+        skip_if_not_root!();
+        let (conn, _, handle) = new_connection().unwrap();
+        tokio::spawn(conn);
+
+        let index = add_dummy_link(&handle, "kata-test-dum2").await;
+        handle.link().set(index).up().execute().await.unwrap();
+        handle
+            .address()
+            .add(index, IpAddr::V4(Ipv4Addr::new(10, 51, 0, 1)), 24)
+            .execute()
+            .await
+            .unwrap();
+
+        let route = RouteSpec {
+            destination: IpNetwork::from_str("10.60.0.0/24").unwrap(),
+            gateway: None,
+        };
+        assert!(add_route(&handle, index, &route).await.is_ok());
+
+        del_link(&handle, index).await;
+    }
+
+    #[tokio::test]
+    async fn test_add_route_v4_with_gateway() {
+        skip_if_not_root!();
+        let (conn, _, handle) = new_connection().unwrap();
+        tokio::spawn(conn);
+
+        let index = add_dummy_link(&handle, "kata-test-dum3").await;
+        handle.link().set(index).up().execute().await.unwrap();
+        handle
+            .address()
+            .add(index, IpAddr::V4(Ipv4Addr::new(10, 52, 0, 1)), 24)
+            .execute()
+            .await
+            .unwrap();
+
+        let route = RouteSpec {
+            destination: IpNetwork::from_str("10.61.0.0/24").unwrap(),
+            gateway: Some(IpAddr::V4(Ipv4Addr::new(10, 52, 0, 2))),
+        };
+        assert!(add_route(&handle, index, &route).await.is_ok());
+
+        del_link(&handle, index).await;
+    }
+}
@@ -7,6 +7,11 @@
 use anyhow::{Context, Result};
 use async_trait::async_trait;
 use nix::sys::{stat, stat::SFlag};
+use serde::{Deserialize, Serialize};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
 use tokio::sync::RwLock;
 
 use super::Volume;
@@ -14,16 +19,47 @@ use crate::volume::utils::{handle_block_volume, DEFAULT_VOLUME_FS_TYPE, KATA_MOU
 use hypervisor::{
     device::{
         device_manager::{do_handle_device, get_block_driver, DeviceManager},
-        DeviceConfig,
+        DeviceConfig, DeviceType,
     },
     BlockConfig,
 };
 
+/// Serializable snapshot of a `BlockVolume`'s device state, used to move a
+/// running sandbox's block devices to another host (the block-device
+/// counterpart of cloud-hypervisor's VmSendMigrationData/
+/// VmReceiveMigrationData). Captures only what's needed to re-register an
+/// equivalent device on the destination and reconnect its guest-facing
+/// mount; the live device itself is left untouched.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct BlockVolumeState {
+    major: i64,
+    minor: i64,
+    driver_option: String,
+    virt_path: String,
+    read_only: bool,
+    destination: String,
+    // The host-guest shared path `new()` generated for this mount via
+    // `generate_shared_path()`, i.e. the value actually used for
+    // `storage.mount_point`/`mount.source`. This is distinct from
+    // `destination`, which is just the OCI mount target.
+    shared_path: String,
+    fs_type: String,
+    options: Vec<String>,
+}
+
 #[derive(Clone)]
 pub(crate) struct BlockVolume {
     storage: Option<agent::Storage>,
     mount: oci::Mount,
     device_id: String,
+    major: i64,
+    minor: i64,
+    driver_option: String,
+    read_only: bool,
+    // Set once this device has been handed off to another host as part of a
+    // live migration. Once set, cleanup() on this (source) instance must
+    // become a no-op, since the destination now owns the device.
+    migrated: Arc<AtomicBool>,
 }
 
 /// BlockVolume for bind-mount block volume
@@ -49,66 +85,32 @@ impl BlockVolume {
             .await
             .context("do handle device failed.")?;
 
-<<<<<<< HEAD
         let block_volume =
             handle_block_volume(device_info, m, read_only, sid, DEFAULT_VOLUME_FS_TYPE)
                 .await
                 .context("do handle block volume failed")?;
-=======
-        // storage
-        let mut storage = agent::Storage {
-            options: if read_only {
-                vec!["ro".to_string()]
-            } else {
-                Vec::new()
-            },
-            ..Default::default()
-        };
-
-        // As the true Block Device wrapped in DeviceType, we need to
-        // get it out from the wrapper, and the device_id will be for
-        // BlockVolume.
-        // safe here, device_info is correct and only unwrap it.
-        let mut device_id = String::new();
-        if let DeviceType::Block(device) = device_info {
-            // blk, mmioblk
-            storage.driver = device.config.driver_option;
-            // /dev/vdX
-            storage.source = device.config.virt_path;
-            device_id = device.device_id;
-        }
-
-        // generate host guest shared path
-        let guest_path = generate_shared_path(m.destination.clone(), read_only, &device_id, sid)
-            .await
-            .context("generate host-guest shared path failed")?;
-        storage.mount_point = guest_path.clone();
-
-        // In some case, dest is device /dev/xxx
-        if m.destination.clone().starts_with("/dev") {
-            storage.fs_type = "bind".to_string();
-            storage.options.append(&mut m.options.clone());
-        } else {
-            // usually, the dest is directory.
-            storage.fs_type = blk_dev_fstype;
-        }
-
-        let mount = oci::Mount {
-            destination: m.destination.clone(),
-            r#type: storage.fs_type.clone(),
-            source: guest_path,
-            options: m.options.clone(),
-            uid_mappings: m.uid_mappings.clone(),
-            gid_mappings: m.gid_mappings.clone(),
-        };
->>>>>>> 70152c24e (tmp commit)
 
         Ok(Self {
             storage: Some(block_volume.0),
             mount: block_volume.1,
             device_id: block_volume.2,
+            major: block_device_config.major,
+            minor: block_device_config.minor,
+            driver_option: block_device_config.driver_option,
+            read_only,
+            migrated: Arc::new(AtomicBool::new(false)),
         })
     }
+
+    /// Mark this volume's device as handed off to a migration destination.
+    ///
+    /// Must only be called once the destination has confirmed it received
+    /// and applied the state from `save_state()` - calling it any earlier
+    /// (e.g. right after `save_state()` returns) would make `cleanup()` a
+    /// permanent no-op on the source even if the hand-off never completes.
+    pub(crate) fn confirm_migrated(&self) {
+        self.migrated.store(true, Ordering::SeqCst);
+    }
 }
 
 #[async_trait]
@@ -128,6 +130,12 @@ impl Volume for BlockVolume {
     }
 
     async fn cleanup(&self, device_manager: &RwLock<DeviceManager>) -> Result<()> {
+        // Ownership of the device has moved to the destination of a live
+        // migration; the source must not tear it down.
+        if self.migrated.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+
         device_manager
             .write()
             .await
@@ -138,6 +146,175 @@ impl Volume for BlockVolume {
     fn get_device_id(&self) -> Result<Option<String>> {
         Ok(Some(self.device_id.clone()))
     }
+
+    fn save_state(&self) -> Result<Vec<u8>> {
+        let state = BlockVolumeState {
+            major: self.major,
+            minor: self.minor,
+            driver_option: self.driver_option.clone(),
+            virt_path: self
+                .storage
+                .as_ref()
+                .map(|s| s.source.clone())
+                .unwrap_or_default(),
+            read_only: self.read_only,
+            destination: self.mount.destination.clone(),
+            shared_path: self
+                .storage
+                .as_ref()
+                .map(|s| s.mount_point.clone())
+                .unwrap_or_default(),
+            fs_type: self.mount.r#type.clone(),
+            options: self.mount.options.clone(),
+        };
+
+        // Serializing the state here is only a local snapshot: the
+        // destination has not actually received anything yet, so this
+        // instance still owns the device. Don't touch `migrated` until the
+        // caller confirms the hand-off via `confirm_migrated()`.
+        serde_json::to_vec(&state).context("failed to serialize block volume state")
+    }
+
+    async fn restore_state(&mut self, d: &RwLock<DeviceManager>, state: &[u8]) -> Result<()> {
+        let state: BlockVolumeState =
+            serde_json::from_slice(state).context("failed to parse block volume state")?;
+
+        let block_device_config = BlockConfig {
+            major: state.major,
+            minor: state.minor,
+            driver_option: state.driver_option,
+            ..Default::default()
+        };
+
+        // re-create and insert the block device into the destination's Kata VM
+        let device_info = do_handle_device(d, &DeviceConfig::BlockCfg(block_device_config.clone()))
+            .await
+            .context("do handle device failed while restoring block volume")?;
+
+        let mut storage = agent::Storage {
+            options: if state.read_only {
+                vec!["ro".to_string()]
+            } else {
+                Vec::new()
+            },
+            fs_type: state.fs_type.clone(),
+            // Reuse the shared path `new()` generated on the source, rather
+            // than the OCI destination: that's what `mount.source` and
+            // `storage.mount_point` are actually populated with, and the
+            // guest-side mount pipeline expects them to agree with it.
+            mount_point: state.shared_path.clone(),
+            ..Default::default()
+        };
+
+        let mut device_id = String::new();
+        if let DeviceType::Block(device) = device_info {
+            storage.driver = device.config.driver_option;
+            // the guest device path (/dev/vdX) assigned on the destination
+            storage.source = device.config.virt_path;
+            device_id = device.device_id;
+        }
+
+        let mount = oci::Mount {
+            destination: state.destination,
+            r#type: storage.fs_type.clone(),
+            source: state.shared_path.clone(),
+            options: state.options,
+            // Id mappings describe the sandbox's user namespace, not this
+            // device's state, so they aren't captured in `BlockVolumeState`;
+            // the destination-side `self.mount` must already carry the
+            // mappings the restored sandbox expects.
+            uid_mappings: self.mount.uid_mappings.clone(),
+            gid_mappings: self.mount.gid_mappings.clone(),
+        };
+
+        self.major = block_device_config.major;
+        self.minor = block_device_config.minor;
+        self.driver_option = block_device_config.driver_option;
+        self.storage = Some(storage);
+        self.mount = mount;
+        self.device_id = device_id;
+        self.migrated.store(false, Ordering::SeqCst);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_block_volume() -> BlockVolume {
+        BlockVolume {
+            storage: Some(agent::Storage {
+                source: "/dev/vda".to_string(),
+                mount_point: "/run/kata-containers/shared/containers/vol".to_string(),
+                ..Default::default()
+            }),
+            mount: oci::Mount {
+                destination: "/data".to_string(),
+                r#type: "bind".to_string(),
+                source: "/dev/vda".to_string(),
+                options: vec![],
+                uid_mappings: vec![],
+                gid_mappings: vec![],
+            },
+            device_id: "vol-0".to_string(),
+            major: 253,
+            minor: 0,
+            driver_option: "virtio-blk".to_string(),
+            read_only: false,
+            migrated: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    #[test]
+    fn test_block_volume_state_serde_roundtrip() {
+        let state = BlockVolumeState {
+            major: 253,
+            minor: 0,
+            driver_option: "virtio-blk".to_string(),
+            virt_path: "/dev/vda".to_string(),
+            read_only: false,
+            destination: "/data".to_string(),
+            shared_path: "/run/kata-containers/shared/containers/vol".to_string(),
+            fs_type: "ext4".to_string(),
+            options: vec!["ro".to_string()],
+        };
+
+        let bytes = serde_json::to_vec(&state).unwrap();
+        let restored: BlockVolumeState = serde_json::from_slice(&bytes).unwrap();
+
+        assert_eq!(state.major, restored.major);
+        assert_eq!(state.minor, restored.minor);
+        assert_eq!(state.driver_option, restored.driver_option);
+        assert_eq!(state.virt_path, restored.virt_path);
+        assert_eq!(state.read_only, restored.read_only);
+        assert_eq!(state.destination, restored.destination);
+        assert_eq!(state.shared_path, restored.shared_path);
+        assert_eq!(state.fs_type, restored.fs_type);
+        assert_eq!(state.options, restored.options);
+    }
+
+    // `cleanup()` becomes a no-op purely by checking `migrated` before
+    // touching the device manager, so that guard condition is exercised
+    // directly here: constructing a real `DeviceManager` to call
+    // `cleanup()` end-to-end needs the hypervisor crate's device wiring,
+    // which is out of scope for this volume-only test.
+    #[test]
+    fn test_migrated_flag_only_set_by_confirm_migrated() {
+        let vol = test_block_volume();
+
+        assert!(!vol.migrated.load(Ordering::SeqCst));
+
+        vol.save_state().unwrap();
+        assert!(
+            !vol.migrated.load(Ordering::SeqCst),
+            "save_state() must not flip migrated on its own"
+        );
+
+        vol.confirm_migrated();
+        assert!(vol.migrated.load(Ordering::SeqCst));
+    }
 }
 
 pub(crate) fn is_block_volume(m: &oci::Mount) -> bool {
@@ -0,0 +1,47 @@
+// Copyright (c) 2019-2022 Alibaba Cloud
+// Copyright (c) 2019-2022 Ant Group
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+
+mod block_volume;
+
+pub(crate) use block_volume::{is_block_volume, BlockVolume};
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use tokio::sync::RwLock;
+
+use hypervisor::device::device_manager::DeviceManager;
+
+/// A sandbox-visible volume backed by some host resource (block device, bind
+/// mount, ...). Implementations own whatever device/mount state they need to
+/// answer `get_volume_mount()`/`get_storage()` and to tear themselves down.
+#[async_trait]
+pub(crate) trait Volume: Send + Sync {
+    fn get_volume_mount(&self) -> Result<Vec<oci::Mount>>;
+
+    fn get_storage(&self) -> Result<Vec<agent::Storage>>;
+
+    async fn cleanup(&self, device_manager: &RwLock<DeviceManager>) -> Result<()>;
+
+    fn get_device_id(&self) -> Result<Option<String>>;
+
+    /// Serialize this volume's device state for a live migration, so it can
+    /// be re-created on the destination via `restore_state()`. Volume types
+    /// that don't support migration can leave this unimplemented.
+    fn save_state(&self) -> Result<Vec<u8>> {
+        Err(anyhow!("save_state is not supported for this volume type"))
+    }
+
+    /// Recreate this volume's device from a `save_state()` snapshot, on the
+    /// live-migration destination. Volume types that don't support migration
+    /// can leave this unimplemented.
+    async fn restore_state(
+        &mut self,
+        _device_manager: &RwLock<DeviceManager>,
+        _state: &[u8],
+    ) -> Result<()> {
+        Err(anyhow!("restore_state is not supported for this volume type"))
+    }
+}